@@ -7,6 +7,7 @@ use core::fmt::Debug;
 use std::collections::{BTreeSet, HashMap};
 use std::hash::Hash;
 use std::ops::{self, Add, Sub};
+use std::sync::Arc;
 
 use epoched::{
     DynEpochOffset, Epoched, EpochedDelta, OffsetPipelineLen,
@@ -19,28 +20,61 @@ use types::{
 };
 
 use crate::btree_set::BTreeSetShims;
-use crate::types::{Bond, BondId, WeightedValidator};
+use crate::types::{Bond, BondId, Unbond, WeightedValidator};
+
+/// A public key that can verify a signature over a message. Implemented by
+/// [`Pos::PublicKey`] to check a validator's proof-of-possession of the
+/// corresponding [`Pos::Signature`].
+pub trait VerifySignature<Signature> {
+    fn verify(&self, signature: &Signature, message: &[u8]) -> bool;
+}
+
+/// Build the domain-separated message that a validator must sign with their
+/// consensus key to prove possession of it: `params.pos_pk_pop_domain_tag`
+/// (e.g. a `b"consensus-key-pop"` prefix, distinguishing this from ordinary
+/// consensus messages) concatenated with the validator's `address`. Since
+/// the tag lives in `PosParams`, distinct chains produce distinct valid
+/// proofs and a signature cannot be replayed from one chain, or context, to
+/// another.
+fn proof_of_possession_message<Address>(
+    params: &PosParams,
+    address: &Address,
+) -> Vec<u8>
+where
+    Address: Debug,
+{
+    let mut message = params.pos_pk_pop_domain_tag.clone();
+    message.extend_from_slice(format!("{:?}", address).as_bytes());
+    message
+}
 
 pub trait Pos {
     type Address: Debug + Clone + PartialEq + Eq + PartialOrd + Ord + Hash;
     type TokenAmount: Debug
         + Clone
         + Copy
-        + Add
-        + Sub
+        + Default
+        + Add<Output = Self::TokenAmount>
+        + Sub<Output = Self::TokenAmount>
         + Into<u64>
         + Into<Self::TokenChange>;
     type TokenChange: Debug
         + Clone
         + Copy
         + Add<Output = Self::TokenChange>
-        + Sub
+        + Sub<Output = Self::TokenChange>
         + From<Self::TokenAmount>
         + Into<i128>;
-    type PublicKey: Debug + Clone;
+    type PublicKey: Debug + Clone + VerifySignature<Self::Signature>;
+    type Signature: Debug + Clone;
 
     /// Address of the PoS account
     const POS_ADDRESS: Self::Address;
+    /// Address of the staking token, passed as the `token` argument to
+    /// [`Pos::transfer`] whenever bonded or unbonded tokens move. Distinct
+    /// from [`Pos::POS_ADDRESS`], which identifies the PoS module account
+    /// that holds those tokens in escrow, not the token itself.
+    const STAKING_TOKEN_ADDRESS: Self::Address;
 
     // TODO it may be nicer to instead provide generic functions for storage
     // write/read and a way for implementors to assign storage keys and convert
@@ -84,9 +118,14 @@ pub trait Pos {
         key: &BondId<Self::Address>,
         value: Epoched<Bond<Self::TokenAmount>, OffsetPipelineLen>,
     );
+    fn write_unbond(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: Epoched<Unbond<Self::TokenAmount>, OffsetUnboundingLen>,
+    );
     fn write_validator_set(
         &mut self,
-        value: Epoched<ValidatorSet<Self::Address>, OffsetUnboundingLen>,
+        value: Epoched<Arc<ValidatorSet<Self::Address>>, OffsetUnboundingLen>,
     );
     fn write_total_voting_power(
         &mut self,
@@ -118,9 +157,28 @@ pub trait Pos {
         &mut self,
         key: &BondId<Self::Address>,
     ) -> Option<Epoched<Bond<Self::TokenAmount>, OffsetPipelineLen>>;
+    fn read_unbond(
+        &mut self,
+        key: &BondId<Self::Address>,
+    ) -> Option<Epoched<Unbond<Self::TokenAmount>, OffsetUnboundingLen>>;
+    /// Read all the bonds delegated or self-bonded to the given validator,
+    /// regardless of their source address.
+    fn read_validator_bonds(
+        &mut self,
+        validator: &Self::Address,
+    ) -> Vec<(
+        BondId<Self::Address>,
+        Epoched<Bond<Self::TokenAmount>, OffsetPipelineLen>,
+    )>;
     fn read_validator_set(
         &mut self,
-    ) -> Epoched<ValidatorSet<Self::Address>, OffsetUnboundingLen>;
+    ) -> Epoched<Arc<ValidatorSet<Self::Address>>, OffsetUnboundingLen>;
+    /// Cheap, read-only access to the validator set at the current epoch: a
+    /// single shared [`Arc`] clone, rather than the whole epoched window of
+    /// `Arc`s that [`Pos::read_validator_set`] returns. Implementors
+    /// should hand out a clone of the same `Arc` held in storage for the
+    /// current epoch.
+    fn read_validator_set_ref(&self) -> Arc<ValidatorSet<Self::Address>>;
     fn read_total_voting_power(
         &mut self,
     ) -> Epoched<Bond<Self::TokenAmount>, OffsetPipelineLen>;
@@ -128,6 +186,7 @@ pub trait Pos {
     fn transfer(
         &mut self,
         token: &Self::Address,
+        amount: Self::TokenAmount,
         source: &Self::Address,
         target: &Self::Address,
     );
@@ -135,7 +194,17 @@ pub trait Pos {
     /// Initialize the PoS system storage data in the genesis block for the
     /// given PoS parameters and initial validator set. The validators'
     /// tokens will be put into self-bonds. The given PoS parameters are written
-    /// with the [`Pos::write_params`] method.
+    /// with the [`Pos::write_params`] method. Each validator must carry a
+    /// valid proof-of-possession of its consensus key (see
+    /// [`Pos::become_validator`]).
+    ///
+    /// This builds and validates the full [`GenesisData`] with
+    /// [`init_genesis_data`] before writing anything to storage, then
+    /// persists it with [`Pos::commit_genesis`]. Callers that want to
+    /// inspect or assert on the derived `ValidatorSet` and
+    /// `total_voting_power` themselves, rather than only running the
+    /// combined build-and-write, can call [`init_genesis_data`] and
+    /// [`Pos::commit_genesis`] directly instead.
     fn init_genesis(
         &mut self,
         params: &PosParams,
@@ -144,21 +213,49 @@ pub trait Pos {
                 Self::Address,
                 Self::TokenAmount,
                 Self::PublicKey,
+                Self::Signature,
             >],
         >,
         current_epoch: Epoch,
-    ) {
+    ) -> Result<(), GenesisError<Self::Address>> {
+        let genesis_data = init_genesis_data(
+            params,
+            validators.as_ref().iter(),
+            current_epoch,
+        )?;
         self.write_params(params);
+        self.commit_genesis(genesis_data);
+        Ok(())
+    }
 
+    /// Persist an already-built, already-validated [`GenesisData`] (see
+    /// [`init_genesis_data`]) to storage. Split out of [`Pos::init_genesis`]
+    /// so that a caller can construct and inspect the genesis state before
+    /// committing it.
+    fn commit_genesis<Validators>(
+        &mut self,
+        genesis_data: GenesisData<
+            Validators,
+            Self::Address,
+            Self::TokenAmount,
+            Self::TokenChange,
+            Self::PublicKey,
+        >,
+    ) where
+        Validators: Iterator<
+            Item = GenesisValidatorData<
+                Self::Address,
+                Self::TokenAmount,
+                Self::TokenChange,
+                Self::PublicKey,
+            >,
+        >,
+    {
         let GenesisData {
             validators,
             validator_set,
             total_voting_power,
-        } = init_genesis_data(
-            params,
-            validators.as_ref().iter(),
-            current_epoch,
-        );
+        } = genesis_data;
 
         validators.for_each(
             |GenesisValidatorData {
@@ -185,12 +282,16 @@ pub trait Pos {
         self.write_total_voting_power(total_voting_power);
     }
 
-    /// Attempt to update the given account to become a validator.
+    /// Attempt to update the given account to become a validator. The
+    /// caller must prove possession of `consensus_key`'s secret key by
+    /// supplying a `proof_of_possession`: a signature over the
+    /// domain-separated message built by [`proof_of_possession_message`].
     fn become_validator(
         &mut self,
         address: &Self::Address,
         staking_reward_address: &Self::Address,
         consensus_key: &Self::PublicKey,
+        proof_of_possession: &Self::Signature,
         current_epoch: Epoch,
     ) -> Result<(), BecomeValidatorError> {
         let params = self.read_params();
@@ -205,9 +306,10 @@ pub trait Pos {
             &params,
             address,
             consensus_key,
+            proof_of_possession,
             &mut validator_set,
             current_epoch,
-        );
+        )?;
         self.write_validator_staking_reward_address(
             address,
             staking_reward_address.clone(),
@@ -223,6 +325,343 @@ pub trait Pos {
     fn is_validator(&mut self, address: &Self::Address) -> bool {
         self.read_validator_state(address).is_some()
     }
+
+    /// An immutable-borrow accessor for the current validator set, for
+    /// read-only consumers (e.g. voting-power queries) that don't need to
+    /// go through the mutable, copy-on-write [`Pos::read_validator_set`]
+    /// path. See [`Pos::read_validator_set_ref`].
+    fn validator_set_ref(&self) -> Arc<ValidatorSet<Self::Address>> {
+        self.read_validator_set_ref()
+    }
+
+    /// The voting power of every active validator at the given `epoch`.
+    /// Walks the active half of the [`ValidatorSet`] at `epoch`, which must
+    /// fall within the bounded window that [`Epoched`] retains, i.e. no
+    /// further back than `current_epoch - unbonding_len` nor further ahead
+    /// than `current_epoch + unbonding_len`. Returns `None` if `epoch` falls
+    /// outside that window, since the caller fully controls `epoch` and an
+    /// out-of-range query must not be allowed to crash the process.
+    ///
+    /// When `epoch` is `current_epoch`, this takes the cheap
+    /// [`Pos::validator_set_ref`] path instead of [`Pos::read_validator_set`],
+    /// since that's the common case for voting-power queries and doesn't
+    /// need the mutable, copy-on-write machinery `Epoched` uses to track
+    /// other epochs in the window.
+    fn validator_stakes_at_epoch(
+        &mut self,
+        current_epoch: Epoch,
+        epoch: Epoch,
+    ) -> Option<HashMap<Self::Address, VotingPower>> {
+        if epoch == current_epoch {
+            let validator_set = self.validator_set_ref();
+            return Some(
+                validator_set
+                    .active
+                    .iter()
+                    .map(|validator| {
+                        (validator.address.clone(), validator.voting_power)
+                    })
+                    .collect(),
+            );
+        }
+        let validator_set = self.read_validator_set();
+        let validator_set = validator_set.get(epoch)?;
+        Some(
+            validator_set
+                .active
+                .iter()
+                .map(|validator| {
+                    (validator.address.clone(), validator.voting_power)
+                })
+                .collect(),
+        )
+    }
+
+    /// The self-bonded and delegated stake of `validator` at `epoch`,
+    /// `(self_bonded, delegated)`. Reads every [`Bond`] recorded against the
+    /// validator, classifies each by its [`BondId`] (self-bonded when
+    /// `source == validator`, delegated otherwise), and sums the deltas that
+    /// have matured by `epoch`. Returns `None` if `epoch` falls outside the
+    /// bonded window that [`Epoched`] retains for any of the validator's
+    /// bonds, matching [`Pos::validator_stakes_at_epoch`]: an out-of-range
+    /// `epoch` must not be silently folded into a "no stake" `(0, 0)`
+    /// result, since that's indistinguishable from a validator that
+    /// genuinely has none.
+    fn validator_self_and_delegated_stake(
+        &mut self,
+        validator: &Self::Address,
+        epoch: Epoch,
+    ) -> Option<(Self::TokenAmount, Self::TokenAmount)> {
+        let mut self_bonded = Self::TokenAmount::default();
+        let mut delegated = Self::TokenAmount::default();
+        for (bond_id, bond) in self.read_validator_bonds(validator) {
+            let bond_at_epoch = bond.get(epoch)?;
+            let matured = bond_at_epoch
+                .delta
+                .iter()
+                .filter(|(bond_epoch, _)| **bond_epoch <= epoch)
+                .fold(Self::TokenAmount::default(), |sum, (_, amount)| {
+                    sum + *amount
+                });
+            if bond_id.source == *validator {
+                self_bonded = self_bonded + matured;
+            } else {
+                delegated = delegated + matured;
+            }
+        }
+        Some((self_bonded, delegated))
+    }
+
+    /// Bond tokens from a source address to a validator, who may be the same
+    /// as the source address. The bonded amount is added to the `Bond` at
+    /// [`DynEpochOffset::PipelineLen`] from the current epoch and the
+    /// validator's total deltas, voting power and position in the
+    /// [`ValidatorSet`] are updated at the same offset, so that the bond only
+    /// starts contributing once the pipeline delay has elapsed.
+    fn bond(
+        &mut self,
+        source: &Self::Address,
+        validator: &Self::Address,
+        amount: Self::TokenAmount,
+        current_epoch: Epoch,
+    ) -> Result<(), BondError> {
+        let params = self.read_params();
+        if !self.is_validator(validator) {
+            return Err(BondError::NotAValidator);
+        }
+        let bond_id = BondId {
+            source: source.clone(),
+            validator: validator.clone(),
+        };
+        let mut bond = self.read_bond(&bond_id).unwrap_or_else(|| {
+            Epoched::init(
+                Bond {
+                    delta: HashMap::default(),
+                },
+                current_epoch,
+                &params,
+            )
+        });
+        let mut total_deltas = self
+            .read_validator_total_deltas(validator)
+            .expect("a validator must have total deltas");
+        let mut voting_power = self
+            .read_validator_voting_power(validator)
+            .expect("a validator must have voting power");
+        let mut validator_set = self.read_validator_set();
+
+        bond_tokens(
+            &params,
+            validator,
+            amount,
+            &mut bond,
+            &mut total_deltas,
+            &mut voting_power,
+            &mut validator_set,
+            current_epoch,
+        );
+
+        self.write_bond(&bond_id, bond);
+        self.write_validator_total_deltas(validator, total_deltas);
+        self.write_validator_voting_power(validator, voting_power);
+        self.write_validator_set(validator_set);
+        Ok(())
+    }
+
+    /// Unbond tokens previously bonded from a source address to a validator.
+    /// The amount is subtracted from the most recent bond deltas and the
+    /// same amount becomes withdrawable, recorded in a new [`Unbond`] at
+    /// [`DynEpochOffset::UnboundingLen`] from the current epoch. The
+    /// validator's total deltas, voting power and position in the
+    /// [`ValidatorSet`] are decremented at the same, later offset, matching
+    /// the delay before the unbonded stake stops backing the validator.
+    fn unbond(
+        &mut self,
+        source: &Self::Address,
+        validator: &Self::Address,
+        amount: Self::TokenAmount,
+        current_epoch: Epoch,
+    ) -> Result<(), UnbondError> {
+        let params = self.read_params();
+        let bond_id = BondId {
+            source: source.clone(),
+            validator: validator.clone(),
+        };
+        let mut bond =
+            self.read_bond(&bond_id).ok_or(UnbondError::NoBondFound)?;
+        let mut unbond = self.read_unbond(&bond_id).unwrap_or_else(|| {
+            Epoched::init(
+                Unbond {
+                    deltas: HashMap::default(),
+                },
+                current_epoch,
+                &params,
+            )
+        });
+        let mut total_deltas = self
+            .read_validator_total_deltas(validator)
+            .ok_or(UnbondError::NotAValidator)?;
+        let mut voting_power = self
+            .read_validator_voting_power(validator)
+            .ok_or(UnbondError::NotAValidator)?;
+        let mut validator_set = self.read_validator_set();
+
+        unbond_tokens(
+            &params,
+            validator,
+            amount,
+            &mut bond,
+            &mut unbond,
+            &mut total_deltas,
+            &mut voting_power,
+            &mut validator_set,
+            current_epoch,
+        )?;
+
+        self.write_bond(&bond_id, bond);
+        self.write_unbond(&bond_id, unbond);
+        self.write_validator_total_deltas(validator, total_deltas);
+        self.write_validator_voting_power(validator, voting_power);
+        self.write_validator_set(validator_set);
+        Ok(())
+    }
+
+    /// Transfer any unbonded tokens for the given bond that have matured (are
+    /// at or before the current epoch) back to the source address, via
+    /// [`Pos::transfer`]. Returns the total amount withdrawn.
+    fn withdraw_unbonded(
+        &mut self,
+        source: &Self::Address,
+        validator: &Self::Address,
+        current_epoch: Epoch,
+    ) -> Result<Self::TokenAmount, WithdrawUnbondedError> {
+        let params = self.read_params();
+        let bond_id = BondId {
+            source: source.clone(),
+            validator: validator.clone(),
+        };
+        let mut unbond = self
+            .read_unbond(&bond_id)
+            .ok_or(WithdrawUnbondedError::NoUnbondFound)?;
+
+        let mut withdrawn = None;
+        unbond.update_from_offset(
+            |unbond| {
+                let matured: Vec<Epoch> = unbond
+                    .deltas
+                    .keys()
+                    .copied()
+                    .filter(|withdrawable_epoch| {
+                        *withdrawable_epoch <= current_epoch
+                    })
+                    .collect();
+                for epoch in matured {
+                    if let Some(amount) = unbond.deltas.remove(&epoch) {
+                        withdrawn = Some(match withdrawn {
+                            Some(total) => total + amount,
+                            None => amount,
+                        });
+                    }
+                }
+            },
+            current_epoch,
+            DynEpochOffset::UnboundingLen,
+            &params,
+        );
+        let withdrawn =
+            withdrawn.ok_or(WithdrawUnbondedError::NoUnbondReady)?;
+
+        self.write_unbond(&bond_id, unbond);
+        self.transfer(
+            &Self::STAKING_TOKEN_ADDRESS,
+            withdrawn,
+            &Self::POS_ADDRESS,
+            source,
+        );
+        Ok(withdrawn)
+    }
+
+    /// Redelegate bonded tokens from one validator to another. Unlike
+    /// composing [`Pos::unbond`] with [`Pos::bond`], this adjusts both
+    /// validators' bonds, total deltas, voting power and position in the
+    /// [`ValidatorSet`] directly via [`redelegate_tokens`], without ever
+    /// recording a withdrawable [`Unbond`] for the source leg, and both legs
+    /// land at the same, shorter `PipelineLen` offset rather than the
+    /// source leg waiting out the full unbonding delay — see
+    /// [`redelegate_tokens`] for why both of those are necessary and what
+    /// they trade off. `dest_validator` is checked upfront so that a bad
+    /// destination can't debit the source leg and then fail the credit
+    /// leg, leaving the owner's tokens stranded mid-redelegation.
+    fn redelegate(
+        &mut self,
+        owner: &Self::Address,
+        src_validator: &Self::Address,
+        dest_validator: &Self::Address,
+        amount: Self::TokenAmount,
+        current_epoch: Epoch,
+    ) -> Result<(), RedelegationError> {
+        if !self.is_validator(dest_validator) {
+            return Err(RedelegationError::Bond(BondError::NotAValidator));
+        }
+        let params = self.read_params();
+        let src_bond_id = BondId {
+            source: owner.clone(),
+            validator: src_validator.clone(),
+        };
+        let dest_bond_id = BondId {
+            source: owner.clone(),
+            validator: dest_validator.clone(),
+        };
+        let mut src_bond = self
+            .read_bond(&src_bond_id)
+            .ok_or(RedelegationError::Unbond(UnbondError::NoBondFound))?;
+        let mut dest_bond = self.read_bond(&dest_bond_id).unwrap_or_else(|| {
+            Epoched::init(
+                Bond {
+                    delta: HashMap::default(),
+                },
+                current_epoch,
+                &params,
+            )
+        });
+        let mut src_total_deltas = self
+            .read_validator_total_deltas(src_validator)
+            .ok_or(RedelegationError::Unbond(UnbondError::NotAValidator))?;
+        let mut dest_total_deltas = self
+            .read_validator_total_deltas(dest_validator)
+            .expect("a validator must have total deltas");
+        let mut src_voting_power = self
+            .read_validator_voting_power(src_validator)
+            .ok_or(RedelegationError::Unbond(UnbondError::NotAValidator))?;
+        let mut dest_voting_power = self
+            .read_validator_voting_power(dest_validator)
+            .expect("a validator must have voting power");
+        let mut validator_set = self.read_validator_set();
+
+        redelegate_tokens(
+            &params,
+            src_validator,
+            dest_validator,
+            amount,
+            &mut src_bond,
+            &mut dest_bond,
+            &mut src_total_deltas,
+            &mut dest_total_deltas,
+            &mut src_voting_power,
+            &mut dest_voting_power,
+            &mut validator_set,
+            current_epoch,
+        )?;
+
+        self.write_bond(&src_bond_id, src_bond);
+        self.write_bond(&dest_bond_id, dest_bond);
+        self.write_validator_total_deltas(src_validator, src_total_deltas);
+        self.write_validator_total_deltas(dest_validator, dest_total_deltas);
+        self.write_validator_voting_power(src_validator, src_voting_power);
+        self.write_validator_voting_power(dest_validator, dest_voting_power);
+        self.write_validator_set(validator_set);
+        Ok(())
+    }
 }
 
 #[allow(missing_docs)]
@@ -230,9 +669,76 @@ pub trait Pos {
 pub enum BecomeValidatorError {
     #[error("The given address is already a validator")]
     AlreadyValidator,
+    #[error(
+        "The given proof of possession is not a valid signature by the \
+         consensus key"
+    )]
+    InvalidProofOfPossession,
 }
 
-struct GenesisData<Validators, Address, TokenAmount, TokenChange, PK>
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum BondError {
+    #[error("The given validator address is not a validator")]
+    NotAValidator,
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum UnbondError {
+    #[error("The given validator address is not a validator")]
+    NotAValidator,
+    #[error("No bond could be found")]
+    NoBondFound,
+    #[error("Trying to unbond more tokens than are bonded")]
+    UnbondAmountGreaterThanBond,
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum WithdrawUnbondedError {
+    #[error("No unbond could be found")]
+    NoUnbondFound,
+    #[error("No unbonded tokens are ready to be withdrawn yet")]
+    NoUnbondReady,
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum RedelegationError {
+    #[error("Unbonding from the source validator failed: {0}")]
+    Unbond(#[from] UnbondError),
+    #[error("Bonding to the destination validator failed: {0}")]
+    Bond(#[from] BondError),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum GenesisError<Address: Debug> {
+    #[error("The initial validator set must not be empty")]
+    NoValidators,
+    #[error(
+        "None of the given validators would remain in the active set after \
+         applying `max_validator_slots`"
+    )]
+    NoActiveValidators,
+    #[error(
+        "Validator {0:?} has a token amount that yields zero voting power"
+    )]
+    ValidatorWithNoVotingPower(Address),
+    #[error(
+        "The proof of possession given for validator {0:?} is not a valid \
+         signature by its consensus key"
+    )]
+    InvalidProofOfPossession(Address),
+}
+
+/// The derived genesis state built (and validated) by [`init_genesis_data`],
+/// ready to be persisted with [`Pos::commit_genesis`]. Exposed so that a
+/// caller can inspect the derived `validator_set` and `total_voting_power`
+/// and assert invariants before committing it, rather than only being able
+/// to run the combined build-and-write of [`Pos::init_genesis`].
+pub struct GenesisData<Validators, Address, TokenAmount, TokenChange, PK>
 where
     Validators: Iterator<
         Item = GenesisValidatorData<Address, TokenAmount, TokenChange, PK>,
@@ -242,26 +748,28 @@ where
     TokenChange: Debug + Copy + ops::Add<Output = TokenChange>,
     PK: Debug + Clone,
 {
-    validators: Validators,
+    pub validators: Validators,
     /// Active and inactive validator sets
-    validator_set: Epoched<ValidatorSet<Address>, OffsetUnboundingLen>,
+    pub validator_set: Epoched<Arc<ValidatorSet<Address>>, OffsetUnboundingLen>,
     /// The sum of all active and inactive validators' voting power
-    total_voting_power: Epoched<VotingPower, OffsetUnboundingLen>,
+    pub total_voting_power: Epoched<VotingPower, OffsetUnboundingLen>,
 }
-struct GenesisValidatorData<Address, TokenAmount, TokenChange, PK>
+
+#[allow(missing_docs)]
+pub struct GenesisValidatorData<Address, TokenAmount, TokenChange, PK>
 where
     Address: Debug + Clone + Ord + Hash,
     TokenAmount: Debug + Clone,
     TokenChange: Debug + Copy + ops::Add<Output = TokenChange>,
     PK: Debug + Clone,
 {
-    address: Address,
-    staking_reward_address: Address,
-    consensus_key: Epoched<PK, OffsetPipelineLen>,
-    state: Epoched<ValidatorState, OffsetPipelineLen>,
-    total_deltas: EpochedDelta<TokenChange, OffsetUnboundingLen>,
-    voting_power: Epoched<VotingPower, OffsetUnboundingLen>,
-    bond: (
+    pub address: Address,
+    pub staking_reward_address: Address,
+    pub consensus_key: Epoched<PK, OffsetPipelineLen>,
+    pub state: Epoched<ValidatorState, OffsetPipelineLen>,
+    pub total_deltas: EpochedDelta<TokenChange, OffsetUnboundingLen>,
+    pub voting_power: Epoched<VotingPower, OffsetUnboundingLen>,
+    pub bond: (
         BondId<Address>,
         Epoched<Bond<TokenAmount>, OffsetPipelineLen>,
     ),
@@ -275,37 +783,71 @@ where
     state: Epoched<ValidatorState, OffsetPipelineLen>,
 }
 
-/// A function that returns genesis data created from the initial validator set.
-fn init_genesis_data<'a, Address, TokenAmount, TokenChange, PK>(
+/// A function that builds and validates the genesis data from the initial
+/// validator set. All validation runs eagerly, before any `GenesisData` is
+/// returned and before any storage is written, so that [`Pos::init_genesis`]
+/// (or a caller going through [`init_genesis_data`] and
+/// [`Pos::commit_genesis`] directly) can bail out on an invalid genesis
+/// without partially persisting it. Rejects an empty validator list, any
+/// validator whose proof-of-possession is not a valid signature over
+/// [`proof_of_possession_message`] by its consensus key (see
+/// [`become_validator_data`]), any validator whose token amount yields zero
+/// [`VotingPower`], and a validator list that would leave the active set
+/// empty once truncated to `params.max_validator_slots`.
+fn init_genesis_data<'a, Address, TokenAmount, TokenChange, PK, Sig>(
     params: &'a PosParams,
-    validators: impl Iterator<Item = &'a GenesisValidator<Address, TokenAmount, PK>>
-    + Clone
+    validators: impl Iterator<
+        Item = &'a GenesisValidator<Address, TokenAmount, PK, Sig>,
+    > + Clone
     + 'a,
     current_epoch: Epoch,
-) -> GenesisData<
-    impl Iterator<
-        Item = GenesisValidatorData<Address, TokenAmount, TokenChange, PK>,
-    > + 'a,
-    Address,
-    TokenAmount,
-    TokenChange,
-    PK,
+) -> Result<
+    GenesisData<
+        impl Iterator<
+            Item = GenesisValidatorData<Address, TokenAmount, TokenChange, PK>,
+        > + 'a,
+        Address,
+        TokenAmount,
+        TokenChange,
+        PK,
+    >,
+    GenesisError<Address>,
 >
 where
     Address: 'a + Debug + Clone + Ord + Hash,
     TokenAmount: 'a + Debug + Clone + Into<u64>,
     TokenChange:
         'a + Debug + Copy + ops::Add<Output = TokenChange> + From<TokenAmount>,
-    PK: 'a + Debug + Clone,
+    PK: 'a + Debug + Clone + VerifySignature<Sig>,
+    Sig: 'a,
 {
+    if validators.clone().next().is_none() {
+        return Err(GenesisError::NoValidators);
+    }
+
     // Accumulate the validator set and total voting power
     let mut active: BTreeSet<WeightedValidator<Address>> = BTreeSet::default();
     let mut total_voting_power = VotingPower::default();
     for GenesisValidator {
-        address, tokens, ..
+        address,
+        tokens,
+        consensus_key,
+        proof_of_possession,
+        ..
     } in validators.clone()
     {
+        let message = proof_of_possession_message(params, address);
+        if !consensus_key.verify(proof_of_possession, &message) {
+            return Err(GenesisError::InvalidProofOfPossession(
+                address.clone(),
+            ));
+        }
         let voting_power = VotingPower::from_tokens(tokens.clone(), params);
+        if voting_power == VotingPower::default() {
+            return Err(GenesisError::ValidatorWithNoVotingPower(
+                address.clone(),
+            ));
+        }
         total_voting_power += voting_power;
         active.insert(WeightedValidator {
             voting_power,
@@ -324,8 +866,12 @@ where
             None => break,
         }
     }
+    if active.is_empty() {
+        return Err(GenesisError::NoActiveValidators);
+    }
     let validator_set = ValidatorSet { active, inactive };
-    let validator_set = Epoched::init_at_genesis(validator_set, current_epoch);
+    let validator_set =
+        Epoched::init_at_genesis(Arc::new(validator_set), current_epoch);
     let total_voting_power =
         Epoched::init_at_genesis(total_voting_power, current_epoch);
 
@@ -337,6 +883,7 @@ where
                   staking_reward_address,
                   tokens,
                   consensus_key,
+                  proof_of_possession: _,
               }| {
             let consensus_key =
                 Epoched::init_at_genesis(consensus_key.clone(), current_epoch);
@@ -369,25 +916,33 @@ where
         },
     );
 
-    GenesisData {
+    Ok(GenesisData {
         validators,
         validator_set,
         total_voting_power,
-    }
+    })
 }
 
-/// A function that initialized data for a new validator.
-fn become_validator_data<Address, PK>(
+/// A function that initialized data for a new validator, after checking
+/// that `proof_of_possession` is a valid signature by `consensus_key` over
+/// the domain-separated [`proof_of_possession_message`] for `address`.
+fn become_validator_data<Address, PK, Sig>(
     params: &PosParams,
     address: &Address,
     consensus_key: &PK,
-    validator_set: &mut Epoched<ValidatorSet<Address>, OffsetUnboundingLen>,
+    proof_of_possession: &Sig,
+    validator_set: &mut Epoched<Arc<ValidatorSet<Address>>, OffsetUnboundingLen>,
     current_epoch: Epoch,
-) -> BecomeValidatorData<PK>
+) -> Result<BecomeValidatorData<PK>, BecomeValidatorError>
 where
     Address: Debug + Clone + Ord + Hash,
-    PK: Debug + Clone,
+    PK: Debug + Clone + VerifySignature<Sig>,
 {
+    let message = proof_of_possession_message(params, address);
+    if !consensus_key.verify(proof_of_possession, &message) {
+        return Err(BecomeValidatorError::InvalidProofOfPossession);
+    }
+
     let consensus_key =
         Epoched::init(consensus_key.clone(), current_epoch, params);
     let mut state =
@@ -395,6 +950,9 @@ where
     state.set(ValidatorState::Candidate, current_epoch, params);
     validator_set.update_from_offset(
         |validator_set| {
+            // `make_mut` only deep-clones the `ValidatorSet` if this
+            // epoch's slot is still shared with another `Arc` holder.
+            let validator_set = Arc::make_mut(validator_set);
             validator_set.inactive.insert(WeightedValidator {
                 voting_power: VotingPower::default(),
                 address: address.clone(),
@@ -405,9 +963,499 @@ where
         params,
     );
 
-    BecomeValidatorData {
+    Ok(BecomeValidatorData {
         consensus_key,
         state,
+    })
+}
+
+/// Apply a bond of `amount` tokens to `validator`, to take effect at
+/// [`DynEpochOffset::PipelineLen`] from `current_epoch`: the bond's deltas,
+/// the validator's total deltas and voting power, and its position in the
+/// `validator_set` are all updated at that same offset.
+#[allow(clippy::too_many_arguments)]
+fn bond_tokens<Address, TokenAmount, TokenChange>(
+    params: &PosParams,
+    validator: &Address,
+    amount: TokenAmount,
+    bond: &mut Epoched<Bond<TokenAmount>, OffsetPipelineLen>,
+    total_deltas: &mut EpochedDelta<TokenChange, OffsetUnboundingLen>,
+    voting_power: &mut Epoched<VotingPower, OffsetUnboundingLen>,
+    validator_set: &mut Epoched<Arc<ValidatorSet<Address>>, OffsetUnboundingLen>,
+    current_epoch: Epoch,
+) where
+    Address: Debug + Clone + Ord + Hash,
+    TokenAmount: Debug + Clone + Copy + Default + Add<Output = TokenAmount>,
+    TokenChange: Debug + Clone + Copy + Add<Output = TokenChange> + From<TokenAmount>,
+{
+    let offset = DynEpochOffset::PipelineLen;
+    let target_epoch = current_epoch + params.pipeline_len;
+
+    bond.update_from_offset(
+        |bond| {
+            let entry =
+                bond.delta.entry(target_epoch).or_insert_with(TokenAmount::default);
+            *entry = *entry + amount;
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+
+    let change = TokenChange::from(amount);
+    total_deltas.update_from_offset(
+        |deltas| *deltas = *deltas + change,
+        current_epoch,
+        offset,
+        params,
+    );
+
+    let delta = VotingPower::from_tokens(amount, params);
+    let mut old_voting_power = None;
+    let mut new_voting_power = None;
+    voting_power.update_from_offset(
+        |power| {
+            old_voting_power = Some(*power);
+            *power += delta;
+            new_voting_power = Some(*power);
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+    let old_voting_power =
+        old_voting_power.expect("voting power must already be initialized");
+    let new_voting_power =
+        new_voting_power.expect("voting power must already be initialized");
+
+    validator_set.update_from_offset(
+        |validator_set| {
+            let validator_set = Arc::make_mut(validator_set);
+            update_validator_set_position(
+                validator_set,
+                validator,
+                old_voting_power,
+                new_voting_power,
+                params,
+            );
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+}
+
+/// Apply an unbond of `amount` tokens from `validator`, subtracting it from
+/// the bond's most recent deltas (most-recently-bonded first) and recording
+/// the same amount as withdrawable in `unbond`, both at
+/// [`DynEpochOffset::UnboundingLen`] from `current_epoch`, alongside the
+/// validator's total deltas, voting power and position in the
+/// `validator_set`, which move at that same offset so none of them can
+/// disagree about how much of the bond is still backing the validator.
+#[allow(clippy::too_many_arguments)]
+fn unbond_tokens<Address, TokenAmount, TokenChange>(
+    params: &PosParams,
+    validator: &Address,
+    amount: TokenAmount,
+    bond: &mut Epoched<Bond<TokenAmount>, OffsetPipelineLen>,
+    unbond: &mut Epoched<Unbond<TokenAmount>, OffsetUnboundingLen>,
+    total_deltas: &mut EpochedDelta<TokenChange, OffsetUnboundingLen>,
+    voting_power: &mut Epoched<VotingPower, OffsetUnboundingLen>,
+    validator_set: &mut Epoched<Arc<ValidatorSet<Address>>, OffsetUnboundingLen>,
+    current_epoch: Epoch,
+) -> Result<(), UnbondError>
+where
+    Address: Debug + Clone + Ord + Hash,
+    TokenAmount:
+        Debug + Clone + Copy + Default + Into<u64> + Sub<Output = TokenAmount>,
+    TokenChange: Debug + Clone + Copy + Sub<Output = TokenChange> + From<TokenAmount>,
+{
+    let unbonding_offset = DynEpochOffset::UnboundingLen;
+
+    let mut insufficient_bond = false;
+    bond.update_from_offset(
+        |bond| {
+            if deduct_bond_deltas(&mut bond.delta, amount).is_err() {
+                insufficient_bond = true;
+            }
+        },
+        current_epoch,
+        unbonding_offset,
+        params,
+    );
+    if insufficient_bond {
+        return Err(UnbondError::UnbondAmountGreaterThanBond);
+    }
+
+    let withdrawable_epoch = current_epoch + params.unbonding_len;
+    unbond.update_from_offset(
+        |unbond| {
+            let entry = unbond
+                .deltas
+                .entry(withdrawable_epoch)
+                .or_insert_with(TokenAmount::default);
+            *entry = *entry + amount;
+        },
+        current_epoch,
+        unbonding_offset,
+        params,
+    );
+
+    let change = TokenChange::from(amount);
+    total_deltas.update_from_offset(
+        |deltas| *deltas = *deltas - change,
+        current_epoch,
+        unbonding_offset,
+        params,
+    );
+
+    let delta = VotingPower::from_tokens(amount, params);
+    let mut old_voting_power = None;
+    let mut new_voting_power = None;
+    voting_power.update_from_offset(
+        |power| {
+            old_voting_power = Some(*power);
+            *power -= delta;
+            new_voting_power = Some(*power);
+        },
+        current_epoch,
+        unbonding_offset,
+        params,
+    );
+    let old_voting_power =
+        old_voting_power.expect("voting power must already be initialized");
+    let new_voting_power =
+        new_voting_power.expect("voting power must already be initialized");
+
+    validator_set.update_from_offset(
+        |validator_set| {
+            let validator_set = Arc::make_mut(validator_set);
+            update_validator_set_position(
+                validator_set,
+                validator,
+                old_voting_power,
+                new_voting_power,
+                params,
+            );
+        },
+        current_epoch,
+        unbonding_offset,
+        params,
+    );
+    Ok(())
+}
+
+/// Subtract `amount` from `deltas`, most-recently-bonded entries first,
+/// removing an entry entirely once it's been fully consumed. Shared by
+/// [`unbond_tokens`] and [`redelegate_tokens`], which both need to deduct
+/// from a bond's deltas the same way. Returns an error if `amount` exceeds
+/// the sum of `deltas`, leaving `deltas` untouched.
+fn deduct_bond_deltas<BondEpoch, TokenAmount>(
+    deltas: &mut HashMap<BondEpoch, TokenAmount>,
+    amount: TokenAmount,
+) -> Result<(), ()>
+where
+    BondEpoch: Copy + Eq + Hash + Ord,
+    TokenAmount:
+        Debug + Clone + Copy + Default + Into<u64> + Sub<Output = TokenAmount>,
+{
+    let total_available: u64 = deltas
+        .values()
+        .fold(0u64, |sum, delta| sum + Into::<u64>::into(*delta));
+    let requested: u64 = amount.into();
+    if requested > total_available {
+        return Err(());
+    }
+
+    let mut bonded_epochs: Vec<BondEpoch> = deltas.keys().copied().collect();
+    bonded_epochs.sort_unstable_by(|a, b| b.cmp(a));
+    let mut remaining = amount;
+    for bonded_epoch in bonded_epochs {
+        let remaining_u64: u64 = remaining.into();
+        if remaining_u64 == 0 {
+            break;
+        }
+        let entry = deltas
+            .get_mut(&bonded_epoch)
+            .expect("key was just read from this map");
+        let available: u64 = (*entry).into();
+        if available <= remaining_u64 {
+            remaining = remaining - *entry;
+            deltas.remove(&bonded_epoch);
+        } else {
+            *entry = *entry - remaining;
+            remaining = TokenAmount::default();
+        }
+    }
+    Ok(())
+}
+
+/// Move a bond of `amount` tokens directly from `src_validator` to
+/// `dest_validator` for the same owner, at [`DynEpochOffset::PipelineLen`]
+/// from `current_epoch` for both legs (the same offset [`bond_tokens`]
+/// uses). Unlike composing [`Pos::unbond`] with [`Pos::bond`], this never
+/// writes a withdrawable [`Unbond`] entry for the source leg: the tokens
+/// never leave [`Pos::POS_ADDRESS`], so recording them as withdrawable
+/// there would let the owner later call [`Pos::withdraw_unbonded`] and be
+/// paid the same amount a second time while it's still backing
+/// `dest_validator`.
+///
+/// Both legs deliberately share `PipelineLen` rather than debiting
+/// `src_validator` at the longer [`DynEpochOffset::UnboundingLen`] a plain
+/// [`unbond_tokens`] would use: if the source leg lagged behind the
+/// destination leg's `PipelineLen` credit, the redelegated amount would
+/// count towards both validators' voting power for every epoch in
+/// between, i.e. the same class of stake-duplication bug this function
+/// exists to avoid, just via voting power instead of a token transfer.
+/// The tradeoff is that redelegating away shortens how long the source
+/// validator stays accountable for the amount to `pipeline_len`, instead
+/// of the full `unbonding_len` a real unbond enforces. Callers that need
+/// to preserve the longer accountability window (e.g. to not let a
+/// validator dodge slashing by redelegating away) should decompose the
+/// move into an explicit [`Pos::unbond`] followed by a [`Pos::bond`]
+/// placed after the unbonding delay, rather than using
+/// [`Pos::redelegate`].
+#[allow(clippy::too_many_arguments)]
+fn redelegate_tokens<Address, TokenAmount, TokenChange>(
+    params: &PosParams,
+    src_validator: &Address,
+    dest_validator: &Address,
+    amount: TokenAmount,
+    src_bond: &mut Epoched<Bond<TokenAmount>, OffsetPipelineLen>,
+    dest_bond: &mut Epoched<Bond<TokenAmount>, OffsetPipelineLen>,
+    src_total_deltas: &mut EpochedDelta<TokenChange, OffsetUnboundingLen>,
+    dest_total_deltas: &mut EpochedDelta<TokenChange, OffsetUnboundingLen>,
+    src_voting_power: &mut Epoched<VotingPower, OffsetUnboundingLen>,
+    dest_voting_power: &mut Epoched<VotingPower, OffsetUnboundingLen>,
+    validator_set: &mut Epoched<Arc<ValidatorSet<Address>>, OffsetUnboundingLen>,
+    current_epoch: Epoch,
+) -> Result<(), UnbondError>
+where
+    Address: Debug + Clone + Ord + Hash,
+    TokenAmount: Debug
+        + Clone
+        + Copy
+        + Default
+        + Into<u64>
+        + Add<Output = TokenAmount>
+        + Sub<Output = TokenAmount>,
+    TokenChange: Debug
+        + Clone
+        + Copy
+        + Add<Output = TokenChange>
+        + Sub<Output = TokenChange>
+        + From<TokenAmount>,
+{
+    let offset = DynEpochOffset::PipelineLen;
+
+    let mut insufficient_bond = false;
+    src_bond.update_from_offset(
+        |bond| {
+            if deduct_bond_deltas(&mut bond.delta, amount).is_err() {
+                insufficient_bond = true;
+            }
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+    if insufficient_bond {
+        return Err(UnbondError::UnbondAmountGreaterThanBond);
+    }
+
+    let target_epoch = current_epoch + params.pipeline_len;
+    dest_bond.update_from_offset(
+        |bond| {
+            let entry =
+                bond.delta.entry(target_epoch).or_insert_with(TokenAmount::default);
+            *entry = *entry + amount;
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+
+    let change = TokenChange::from(amount);
+    src_total_deltas.update_from_offset(
+        |deltas| *deltas = *deltas - change,
+        current_epoch,
+        offset,
+        params,
+    );
+    dest_total_deltas.update_from_offset(
+        |deltas| *deltas = *deltas + change,
+        current_epoch,
+        offset,
+        params,
+    );
+
+    let delta = VotingPower::from_tokens(amount, params);
+    let mut src_old_voting_power = None;
+    let mut src_new_voting_power = None;
+    src_voting_power.update_from_offset(
+        |power| {
+            src_old_voting_power = Some(*power);
+            *power -= delta;
+            src_new_voting_power = Some(*power);
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+    let src_old_voting_power = src_old_voting_power
+        .expect("voting power must already be initialized");
+    let src_new_voting_power = src_new_voting_power
+        .expect("voting power must already be initialized");
+
+    let mut dest_old_voting_power = None;
+    let mut dest_new_voting_power = None;
+    dest_voting_power.update_from_offset(
+        |power| {
+            dest_old_voting_power = Some(*power);
+            *power += delta;
+            dest_new_voting_power = Some(*power);
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+    let dest_old_voting_power = dest_old_voting_power
+        .expect("voting power must already be initialized");
+    let dest_new_voting_power = dest_new_voting_power
+        .expect("voting power must already be initialized");
+
+    validator_set.update_from_offset(
+        |validator_set| {
+            let validator_set = Arc::make_mut(validator_set);
+            update_validator_set_position(
+                validator_set,
+                src_validator,
+                src_old_voting_power,
+                src_new_voting_power,
+                params,
+            );
+            update_validator_set_position(
+                validator_set,
+                dest_validator,
+                dest_old_voting_power,
+                dest_new_voting_power,
+                params,
+            );
+        },
+        current_epoch,
+        offset,
+        params,
+    );
+    Ok(())
+}
+
+/// Move a validator's entry in the `validator_set` to reflect its new
+/// voting power, then re-balance the active/inactive split against
+/// `params.max_validator_slots`, promoting or demoting entries as needed.
+/// Mirrors the truncation done once at genesis in [`init_genesis_data`].
+fn update_validator_set_position<Address>(
+    validator_set: &mut ValidatorSet<Address>,
+    validator: &Address,
+    old_voting_power: VotingPower,
+    new_voting_power: VotingPower,
+    params: &PosParams,
+) where
+    Address: Debug + Clone + Ord + Hash,
+{
+    let old_entry = WeightedValidator {
+        voting_power: old_voting_power,
+        address: validator.clone(),
+    };
+    let was_active = validator_set.active.remove(&old_entry);
+    if !was_active {
+        validator_set.inactive.remove(&old_entry);
+    }
+    let new_entry = WeightedValidator {
+        voting_power: new_voting_power,
+        address: validator.clone(),
+    };
+    if was_active {
+        validator_set.active.insert(new_entry);
+    } else {
+        validator_set.inactive.insert(new_entry);
+    }
+
+    // Re-balance the active/inactive split, which may have shifted due to
+    // the updated voting power.
+    while validator_set.active.len() > params.max_validator_slots as usize {
+        match validator_set.active.pop_first_shim() {
+            Some(smallest_active) => {
+                validator_set.inactive.insert(smallest_active);
+            }
+            None => break,
+        }
+    }
+    while (validator_set.active.len() as u64) < params.max_validator_slots {
+        match validator_set.inactive.iter().next_back().cloned() {
+            Some(largest_inactive) => {
+                validator_set.inactive.remove(&largest_inactive);
+                validator_set.active.insert(largest_inactive);
+            }
+            None => break,
+        }
+    }
+    // Swap entries while the largest inactive validator outweighs the
+    // smallest active one.
+    while let (Some(smallest_active), Some(largest_inactive)) = (
+        validator_set.active.iter().next().cloned(),
+        validator_set.inactive.iter().next_back().cloned(),
+    ) {
+        if largest_inactive <= smallest_active {
+            break;
+        }
+        validator_set.active.remove(&smallest_active);
+        validator_set.inactive.remove(&largest_inactive);
+        validator_set.active.insert(largest_inactive);
+        validator_set.inactive.insert(smallest_active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Unbonding (and redelegating) must draw down the most-recently-bonded
+    /// deltas first, leaving the oldest ones untouched.
+    #[test]
+    fn deduct_bond_deltas_takes_most_recent_first() {
+        let mut deltas: HashMap<u64, u64> =
+            [(1u64, 10u64), (2, 10), (3, 10)].into_iter().collect();
+
+        deduct_bond_deltas(&mut deltas, 15).expect("15 <= 30 available");
+
+        assert_eq!(deltas.get(&1), Some(&10));
+        assert_eq!(deltas.get(&2), Some(&5));
+        assert_eq!(deltas.get(&3), None);
+    }
+
+    /// Deducting more than the sum of all deltas must fail and leave the
+    /// deltas untouched.
+    #[test]
+    fn deduct_bond_deltas_rejects_amount_greater_than_bond() {
+        let mut deltas: HashMap<u64, u64> =
+            [(1u64, 10u64), (2, 5)].into_iter().collect();
+
+        let result = deduct_bond_deltas(&mut deltas, 16);
+
+        assert!(result.is_err());
+        assert_eq!(deltas.get(&1), Some(&10));
+        assert_eq!(deltas.get(&2), Some(&5));
+    }
+
+    /// Deducting exactly the full bonded amount must empty the map.
+    #[test]
+    fn deduct_bond_deltas_can_take_the_full_amount() {
+        let mut deltas: HashMap<u64, u64> =
+            [(1u64, 10u64), (2, 5)].into_iter().collect();
+
+        deduct_bond_deltas(&mut deltas, 15).expect("15 == 15 available");
+
+        assert!(deltas.is_empty());
     }
 }
 